@@ -0,0 +1,90 @@
+#[cfg(test)]
+use std::sync::Mutex;
+#[cfg(test)]
+use std::time::Duration;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Abstracts wall-clock and monotonic time so the flip-timing loop and recording
+/// durations can be unit-tested without real sleeps, following moonfire-nvr's
+/// `Clocks: Send + Sync + 'static` pattern.
+pub trait Clock: Send + Sync + 'static {
+    /// Milliseconds since the Unix epoch.
+    fn now_wall(&self) -> u128;
+    /// A monotonic timestamp suitable for measuring elapsed durations.
+    fn now_mono(&self) -> Instant;
+}
+
+/// The real clock, backed by `SystemTime` and `Instant`.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_wall(&self) -> u128 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+    }
+
+    fn now_mono(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when `advance` is called, so tests can assert the
+/// boost flip happens exactly once per `recording_duration` and that
+/// start times come out right, without wall-clock flakiness. Test-only: there
+/// is no production code path that wants a clock it can freeze.
+#[cfg(test)]
+pub struct FakeClock {
+    base_mono: Instant,
+    offset: Mutex<Duration>,
+}
+
+#[cfg(test)]
+impl FakeClock {
+    pub fn new() -> Self {
+        FakeClock {
+            base_mono: Instant::now(),
+            offset: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.offset.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(test)]
+impl Default for FakeClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+impl Clock for FakeClock {
+    fn now_wall(&self) -> u128 {
+        self.offset.lock().unwrap().as_millis()
+    }
+
+    fn now_mono(&self) -> Instant {
+        self.base_mono + *self.offset.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advance_moves_both_wall_and_mono_time() {
+        let clock = FakeClock::new();
+        let start_mono = clock.now_mono();
+        assert_eq!(clock.now_wall(), 0);
+
+        clock.advance(Duration::from_secs(5));
+
+        assert_eq!(clock.now_wall(), 5_000);
+        assert_eq!(clock.now_mono() - start_mono, Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::recording::EventCounters;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const RESET: &str = "\x1b[0m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const RED: &str = "\x1b[31m";
+
+/// Red once the failed fraction crosses `threshold`, yellow at half that.
+fn color_for_failure_fraction(fraction: f64, threshold: f64) -> &'static str {
+    if fraction >= threshold {
+        RED
+    } else if fraction >= threshold / 2.0 {
+        YELLOW
+    } else {
+        GREEN
+    }
+}
+
+/// Builds one refreshing status line: boost state, elapsed time in the current
+/// window, and per-event success/failure/unparsable rates per second.
+pub fn render_line(
+    boost_enabled: bool,
+    elapsed: Duration,
+    counters: &HashMap<String, EventCounters>,
+    failure_rate_threshold: f64,
+) -> String {
+    let elapsed_secs = elapsed.as_secs_f64().max(1.0 / 1000.0);
+    let mut event_names: Vec<&String> = counters.keys().collect();
+    event_names.sort();
+
+    let mut segments = Vec::with_capacity(event_names.len());
+    for event_name in event_names {
+        let c = &counters[event_name];
+        let failure_fraction = if c.matched == 0 {
+            0.0
+        } else {
+            c.failure as f64 / c.matched as f64
+        };
+        let color = color_for_failure_fraction(failure_fraction, failure_rate_threshold);
+        segments.push(format!(
+            "{}: {}ok={:.1}/s fail={:.1}/s bad={:.1}/s{}",
+            event_name,
+            color,
+            c.success as f64 / elapsed_secs,
+            c.failure as f64 / elapsed_secs,
+            c.unparsable as f64 / elapsed_secs,
+            RESET,
+        ));
+    }
+
+    format!(
+        "\rboost={:<5} elapsed={:>3}s | {}",
+        boost_enabled,
+        elapsed.as_secs(),
+        segments.join("  ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_is_green_below_half_the_threshold() {
+        assert_eq!(color_for_failure_fraction(0.04, 0.1), GREEN);
+    }
+
+    #[test]
+    fn color_is_yellow_at_half_the_threshold() {
+        assert_eq!(color_for_failure_fraction(0.05, 0.1), YELLOW);
+    }
+
+    #[test]
+    fn color_is_red_at_the_threshold() {
+        assert_eq!(color_for_failure_fraction(0.1, 0.1), RED);
+    }
+
+    #[test]
+    fn render_line_includes_boost_state_elapsed_and_each_event() {
+        let mut counters = HashMap::new();
+        counters.insert(
+            "page_alloc".to_string(),
+            EventCounters {
+                matched: 4,
+                success: 3,
+                failure: 1,
+                unparsable: 0,
+            },
+        );
+
+        let line = render_line(true, Duration::from_secs(5), &counters, 0.1);
+
+        assert!(line.contains("boost=true"));
+        assert!(line.contains("elapsed=  5s"));
+        assert!(line.contains("page_alloc:"));
+    }
+
+    #[test]
+    fn render_line_sorts_events_by_name() {
+        let mut counters = HashMap::new();
+        counters.insert("zzz".to_string(), EventCounters::default());
+        counters.insert("aaa".to_string(), EventCounters::default());
+
+        let line = render_line(false, Duration::from_secs(0), &counters, 0.1);
+
+        assert!(line.find("aaa").unwrap() < line.find("zzz").unwrap());
+    }
+}
@@ -0,0 +1,252 @@
+use crate::recording::ParseResult;
+use anyhow::{Context, Result};
+use regex::{Regex, RegexSet};
+use serde::Deserialize;
+
+/// How to turn the text captured for an event into a success/failure verdict.
+///
+/// `NonZeroHex` reproduces the tracer's original behaviour of treating a captured
+/// hex value of zero as a failed allocation; `Always` just records that the event
+/// fired, for tracepoints (sched switches, kmalloc) with nothing to discriminate.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SuccessPredicate {
+    #[default]
+    NonZeroHex,
+    Always,
+}
+
+impl SuccessPredicate {
+    fn evaluate(self, captured: Option<&str>) -> ParseResult {
+        match (self, captured) {
+            (_, None) => ParseResult::Unparsable,
+            (SuccessPredicate::Always, Some(_)) => ParseResult::Successful,
+            (SuccessPredicate::NonZeroHex, Some(raw)) => {
+                match u128::from_str_radix(raw, 16) {
+                    Ok(0) => ParseResult::Failed,
+                    Ok(_) => ParseResult::Successful,
+                    Err(_) => ParseResult::Unparsable,
+                }
+            }
+        }
+    }
+}
+
+/// One named tracepoint the recorder should watch for, as read from `--events-config`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct EventConfigEntry {
+    pub name: String,
+    pub pattern: String,
+    #[serde(default)]
+    pub capture_group: Option<usize>,
+    #[serde(default)]
+    pub success_predicate: SuccessPredicate,
+}
+
+/// A compiled `EventConfigEntry`.
+pub struct EventDef {
+    pub name: String,
+    pub regex: Regex,
+    pub capture_group: Option<usize>,
+    pub success_predicate: SuccessPredicate,
+}
+
+/// The full list of tracked tracepoints, plus a `RegexSet` used to cheaply test
+/// which definitions a line matches before running each matched definition's
+/// full (and pricier) `Regex::captures` call.
+pub struct EventSet {
+    pub defs: Vec<EventDef>,
+    pub regex_set: RegexSet,
+}
+
+impl EventSet {
+    pub fn from_entries(entries: Vec<EventConfigEntry>) -> Result<Self> {
+        let defs = entries
+            .into_iter()
+            .map(|entry| {
+                Ok(EventDef {
+                    regex: Regex::new(&entry.pattern)
+                        .with_context(|| format!("invalid pattern for event `{}`", entry.name))?,
+                    name: entry.name,
+                    capture_group: entry.capture_group,
+                    success_predicate: entry.success_predicate,
+                })
+            })
+            .collect::<Result<Vec<EventDef>>>()?;
+        let regex_set = RegexSet::new(defs.iter().map(|def| def.regex.as_str()))?;
+        Ok(EventSet { defs, regex_set })
+    }
+
+    /// Loads a TOML or JSON list of event definitions from `path`, picking the
+    /// format by file extension. JSON is a bare array of entries; TOML, which
+    /// has no syntax for a bare top-level array, lists them under `[[events]]`.
+    pub fn load(path: &str) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("could not read events config at {}", path))?;
+        let entries: Vec<EventConfigEntry> = if path.ends_with(".json") {
+            serde_json::from_str(&contents)?
+        } else {
+            #[derive(Deserialize)]
+            struct TomlEventsConfig {
+                events: Vec<EventConfigEntry>,
+            }
+            toml::from_str::<TomlEventsConfig>(&contents)?.events
+        };
+        Self::from_entries(entries)
+    }
+
+    /// The tracer's original hardcoded single `page_alloc` tracepoint, used when
+    /// no `--events-config` is given.
+    pub fn default_page_alloc() -> Self {
+        Self::from_entries(vec![EventConfigEntry {
+            name: "page_alloc".to_string(),
+            pattern: r".*page=([A-z0-9]+)\s.*".to_string(),
+            capture_group: Some(1),
+            success_predicate: SuccessPredicate::NonZeroHex,
+        }])
+        .expect("default page_alloc event definition is valid")
+    }
+
+    /// Tests `line` against every definition's pattern via the `RegexSet`, then
+    /// runs the full capture only for definitions that matched, returning each
+    /// matched event's name alongside its outcome.
+    pub fn classify_line(&self, line: &str) -> Vec<(&str, ParseResult)> {
+        self.regex_set
+            .matches(line)
+            .into_iter()
+            .map(|idx| {
+                let def = &self.defs[idx];
+                let captured = def
+                    .regex
+                    .captures(line)
+                    .and_then(|captures| captures.get(def.capture_group.unwrap_or(0)))
+                    .map(|m| m.as_str());
+                (def.name.as_str(), def.success_predicate.evaluate(captured))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_event_set() -> EventSet {
+        EventSet::from_entries(vec![
+            EventConfigEntry {
+                name: "page_alloc".to_string(),
+                pattern: r".*page=([A-z0-9]+)\s.*".to_string(),
+                capture_group: Some(1),
+                success_predicate: SuccessPredicate::NonZeroHex,
+            },
+            EventConfigEntry {
+                name: "sched_switch".to_string(),
+                pattern: r".*sched_switch.*".to_string(),
+                capture_group: None,
+                success_predicate: SuccessPredicate::Always,
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn classify_line_only_matches_the_relevant_event() {
+        let events = two_event_set();
+
+        let matches = events.classify_line("task-1 [000] .... page=deadbeef next\n");
+
+        assert_eq!(matches, vec![("page_alloc", ParseResult::Successful)]);
+    }
+
+    #[test]
+    fn classify_line_treats_zero_hex_as_failed() {
+        let events = two_event_set();
+
+        let matches = events.classify_line("task-1 [000] .... page=0 next\n");
+
+        assert_eq!(matches, vec![("page_alloc", ParseResult::Failed)]);
+    }
+
+    #[test]
+    fn classify_line_can_match_several_events_at_once() {
+        let events = EventSet::from_entries(vec![
+            EventConfigEntry {
+                name: "any_alloc".to_string(),
+                pattern: r".*page=.*".to_string(),
+                capture_group: None,
+                success_predicate: SuccessPredicate::Always,
+            },
+            EventConfigEntry {
+                name: "page_alloc".to_string(),
+                pattern: r".*page=([A-z0-9]+)\s.*".to_string(),
+                capture_group: Some(1),
+                success_predicate: SuccessPredicate::NonZeroHex,
+            },
+        ])
+        .unwrap();
+
+        let matches = events.classify_line("task-1 [000] .... page=deadbeef next\n");
+
+        assert_eq!(matches.len(), 2);
+        assert!(matches.contains(&("any_alloc", ParseResult::Successful)));
+        assert!(matches.contains(&("page_alloc", ParseResult::Successful)));
+    }
+
+    #[test]
+    fn classify_line_ignores_non_matching_lines() {
+        let events = two_event_set();
+
+        let matches = events.classify_line("task-1 [000] .... irrelevant tracepoint\n");
+
+        assert!(matches.is_empty());
+    }
+
+    #[test]
+    fn load_reads_toml_config() {
+        let dir = std::env::temp_dir().join(format!(
+            "rust-atomic-tracer-test-events-{}-load_reads_toml_config",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.toml");
+        std::fs::write(
+            &path,
+            concat!(
+                "[[events]]\n",
+                "name = \"kmalloc\"\n",
+                "pattern = \".*kmalloc.*\"\n",
+                "success_predicate = \"always\"\n"
+            ),
+        )
+        .unwrap();
+
+        let events = EventSet::load(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(events.defs.len(), 1);
+        assert_eq!(events.defs[0].name, "kmalloc");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn load_reads_json_config() {
+        let dir = std::env::temp_dir().join(format!(
+            "rust-atomic-tracer-test-events-{}-load_reads_json_config",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.json");
+        std::fs::write(
+            &path,
+            r#"[{"name": "kmalloc", "pattern": ".*kmalloc.*", "success_predicate": "always"}]"#,
+        )
+        .unwrap();
+
+        let events = EventSet::load(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(events.defs.len(), 1);
+        assert_eq!(events.defs[0].name, "kmalloc");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::recording::{EventCounters, ParseResult};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+struct AtomicEventCounters {
+    matched: AtomicU64,
+    success: AtomicU64,
+    failure: AtomicU64,
+    unparsable: AtomicU64,
+}
+
+impl AtomicEventCounters {
+    fn new() -> Self {
+        AtomicEventCounters {
+            matched: AtomicU64::new(0),
+            success: AtomicU64::new(0),
+            failure: AtomicU64::new(0),
+            unparsable: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self, result: &ParseResult) {
+        self.matched.fetch_add(1, Ordering::Relaxed);
+        match result {
+            ParseResult::Successful => self.success.fetch_add(1, Ordering::Relaxed),
+            ParseResult::Failed => self.failure.fetch_add(1, Ordering::Relaxed),
+            ParseResult::Unparsable => self.unparsable.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    fn snapshot(&self) -> EventCounters {
+        EventCounters {
+            matched: self.matched.load(Ordering::Relaxed) as u128,
+            success: self.success.load(Ordering::Relaxed) as u128,
+            failure: self.failure.load(Ordering::Relaxed) as u128,
+            unparsable: self.unparsable.load(Ordering::Relaxed) as u128,
+        }
+    }
+}
+
+/// Lock-free per-event counters for the currently running recording window, so
+/// the live dashboard in `main` can read progress without pausing the recorder
+/// thread or taking a mutex per line.
+pub struct LiveCounters {
+    by_event: HashMap<String, AtomicEventCounters>,
+}
+
+impl LiveCounters {
+    pub fn new(event_names: impl IntoIterator<Item = String>) -> Self {
+        LiveCounters {
+            by_event: event_names
+                .into_iter()
+                .map(|name| (name, AtomicEventCounters::new()))
+                .collect(),
+        }
+    }
+
+    pub fn record(&self, event_name: &str, result: &ParseResult) {
+        if let Some(counters) = self.by_event.get(event_name) {
+            counters.record(result);
+        }
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, EventCounters> {
+        self.by_event
+            .iter()
+            .map(|(name, counters)| (name.clone(), counters.snapshot()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_tallies_by_outcome() {
+        let counters = LiveCounters::new(["page_alloc".to_string()]);
+
+        counters.record("page_alloc", &ParseResult::Successful);
+        counters.record("page_alloc", &ParseResult::Successful);
+        counters.record("page_alloc", &ParseResult::Failed);
+        counters.record("page_alloc", &ParseResult::Unparsable);
+
+        let snapshot = counters.snapshot();
+        let c = &snapshot["page_alloc"];
+        assert_eq!((c.matched, c.success, c.failure, c.unparsable), (4, 2, 1, 1));
+    }
+
+    #[test]
+    fn record_ignores_unknown_event_names() {
+        let counters = LiveCounters::new(["page_alloc".to_string()]);
+
+        counters.record("sched_switch", &ParseResult::Successful);
+
+        assert!(!counters.snapshot().contains_key("sched_switch"));
+        assert_eq!(counters.snapshot()["page_alloc"].matched, 0);
+    }
+
+    #[test]
+    fn snapshot_tracks_every_configured_event_independently() {
+        let counters = LiveCounters::new(["a".to_string(), "b".to_string()]);
+
+        counters.record("a", &ParseResult::Successful);
+
+        let snapshot = counters.snapshot();
+        assert_eq!(snapshot["a"].matched, 1);
+        assert_eq!(snapshot["b"].matched, 0);
+    }
+}
@@ -1,14 +1,27 @@
+mod clock;
+mod dashboard;
+mod events;
+mod live;
+mod recording;
+mod rotation;
+mod status;
+mod writer;
+
 use anyhow::Result;
 use clap::Parser;
-use regex::Regex;
+use clock::{Clock, SystemClock};
+use events::EventSet;
+use live::LiveCounters;
+use recording::Recording;
+use rotation::RotatingSink;
+use status::{new_shared_status, RecordStatus, SharedRecordStatus};
 use std::fs::File;
-use std::io::Write;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread::sleep;
 use std::time::{Duration, Instant};
-use std::time::{SystemTime, UNIX_EPOCH};
+use writer::{OutputFormat, RecordingRow};
 
 const TRACE_PIPE_PATH: &str = "/sys/kernel/debug/tracing/trace_pipe";
 const DEFAULT_DST_DIR_PATH: &str = "/home/lelloman/monnezza-2";
@@ -27,123 +40,107 @@ struct CliArgs {
 
     #[clap(short, long, default_value_t = DEFAULT_RECORDING_DURATION_SECS)]
     pub recording_duration_secs: u64,
-}
 
-struct Recording {
-    pub start_time: u128,
-    pub enabled: bool,
-    pub successful_allocations: u128,
-    pub failed_allocations: u128,
-    pub unparsed_allocations: u128,
-}
+    #[clap(short, long, value_enum, default_value = "csv")]
+    pub format: OutputFormat,
 
-#[derive(Debug)]
-enum ParseResult {
-    Successful,
-    Failed,
-    Unparsable,
-}
+    /// TOML or JSON file listing the tracepoints to watch for; defaults to the
+    /// single hardcoded `page_alloc` event when omitted.
+    #[clap(short, long)]
+    pub events_config: Option<String>,
 
-fn now_ms() -> u128 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis()
-}
+    /// Print a refreshing colored status line every `main_loop_sleep_secs`
+    /// instead of only logging once a recording is saved.
+    #[clap(long)]
+    pub live: bool,
 
-fn parse_line(line: &String, line_regex: &Regex) -> ParseResult {
-    match line_regex.captures(&line) {
-        None => {
-            //println!("failed to capture line");
-            ParseResult::Unparsable
-        }
-        Some(capture) => match capture.get(1) {
-            None => {
-                //println!("Failed to get parsed line group");
-                ParseResult::Unparsable
-            }
-            Some(captured_group) => match u128::from_str_radix(captured_group.as_str(), 16) {
-                Ok(value) => {
-                    /*println!(
-                        "Captured group: <{}> value: {}",
-                        captured_group.as_str(),
-                        value
-                    );*/
-                    if value == 0 {
-                        ParseResult::Failed
-                    } else {
-                        ParseResult::Successful
-                    }
-                }
-                Err(_) => {
-                    //println!("Failed to parse value {}", captured_group.as_str());
-                    ParseResult::Unparsable
-                }
-            },
-        },
-    }
+    /// Failed-event fraction at which the live dashboard turns a rate red.
+    #[clap(long, default_value_t = 0.1)]
+    pub failure_rate_threshold: f64,
+
+    /// Rotate to a new segment file once the current one exceeds this many bytes.
+    #[clap(long)]
+    pub max_file_bytes: Option<u64>,
+
+    /// Delete the oldest segment file once more than this many accumulate.
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_files: Option<u64>,
 }
 
-fn save_recording_file(dst_dir_path: &str, recording: Recording) {
-    let mut dst_file = std::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .open(format!("{}/{}", dst_dir_path, now_ms().to_string()))
-        .expect("Could not create save file");
-    let recording_string = format!(
-        "start:{}\nend:{}\nenabled:{}\nsuccess:{}\nfailure:{}\nunparsable:{}\n",
-        recording.start_time,
-        now_ms(),
-        recording.enabled,
-        recording.successful_allocations,
-        recording.failed_allocations,
-        recording.unparsed_allocations
-    );
-    dst_file.write_all(recording_string.as_bytes());
+fn save_recording_file(
+    sink: &Mutex<RotatingSink>,
+    recording: &Recording,
+    clock: &dyn Clock,
+) -> Result<()> {
+    if recording.total() == 0 {
+        // Nothing was captured this window (e.g. trace_pipe was silent); skip
+        // writing a row so empty recordings don't litter the output.
+        return Ok(());
+    }
+    let rows = RecordingRow::from_recording(recording, clock.now_wall());
+    let mut sink = sink.lock().unwrap();
+    for row in &rows {
+        sink.write_row(row)?;
+    }
+    Ok(())
 }
 
 fn start_recorder(
-    dst_dir_path: String,
     boost_enabled: bool,
     running: Arc<AtomicBool>,
+    status: SharedRecordStatus,
+    clock: Arc<dyn Clock>,
+    events: Arc<EventSet>,
+    live_counters: Arc<LiveCounters>,
+    sink: Arc<Mutex<RotatingSink>>,
 ) -> Result<RecorderHandle> {
     let trace_pipe = File::open(TRACE_PIPE_PATH).expect("Could not open trace pipe file");
     let mut reader = BufReader::new(trace_pipe);
 
     let mut buf = String::from_utf8(vec![0u8; 4096]).unwrap();
-    let line_regex = Regex::new(r".*page=([A-z0-9]+)\s.*").unwrap();
     return Ok(std::thread::spawn(move || {
-        let mut recording = Recording {
-            start_time: now_ms(),
-            enabled: boost_enabled,
-            successful_allocations: 0,
-            failed_allocations: 0,
-            unparsed_allocations: 0,
-        };
+        *status.lock().unwrap() = RecordStatus::Waiting;
+        let thread_start = clock.now_mono();
+        let mut recording = Recording::new(clock.now_wall(), boost_enabled);
         while running.load(Ordering::SeqCst) {
             buf.clear();
             match reader.read_line(&mut buf) {
                 Ok(_) => {
-                    let parsed_result = parse_line(&buf, &line_regex);                    
-                    match parsed_result {
-                        ParseResult::Successful => recording.successful_allocations += 1,
-                        ParseResult::Failed => recording.failed_allocations += 1,
-                        ParseResult::Unparsable => recording.unparsed_allocations += 1,
+                    for (event_name, result) in events.classify_line(&buf) {
+                        live_counters.record(event_name, &result);
+                        recording.record_event(event_name, result);
                     }
+                    *status.lock().unwrap() =
+                        RecordStatus::Recording(clock.now_mono() - thread_start);
                 }
                 Err(x) => {
                     println!("Error while reading line\n{}", x);
+                    *status.lock().unwrap() = RecordStatus::Error(x.to_string());
                     break;
                 }
             }
         }
 
         println!("Recording stopped, saving file...");
-        save_recording_file(&dst_dir_path, recording);
-        println!("Saved file")
+        match save_recording_file(sink.as_ref(), &recording, clock.as_ref()) {
+            Ok(()) => {
+                *status.lock().unwrap() = RecordStatus::Finished;
+                println!("Saved file")
+            }
+            Err(err) => {
+                *status.lock().unwrap() = RecordStatus::Error(err.to_string());
+                println!("Failed to save recording file\n{}", err)
+            }
+        }
     }));
 }
 
+/// Whether the current recording window has run long enough to flip the boost
+/// state and start a new one.
+fn should_flip(now: Instant, window_start: Instant, recording_duration: Duration) -> bool {
+    now - window_start > recording_duration
+}
+
 fn setup_ctrl(running: Arc<AtomicBool>) {
     ctrlc::set_handler(move || {
         running.store(false, Ordering::SeqCst);
@@ -165,39 +162,82 @@ fn main() {
     let running = Arc::new(AtomicBool::new(true));
     setup_ctrl(running.clone());
 
+    let events = Arc::new(match &cli_args.events_config {
+        Some(path) => EventSet::load(path).expect("Could not load events config"),
+        None => EventSet::default_page_alloc(),
+    });
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
     let recorder_running = Arc::new(AtomicBool::new(true));
+    let record_status = new_shared_status();
     let mut boost_enabled = true;
     set_eboost(boost_enabled);
 
+    let sink = Arc::new(Mutex::new(
+        RotatingSink::new(
+            &cli_args.dst_dir_path,
+            cli_args.format,
+            cli_args.max_file_bytes,
+            cli_args.max_files.map(|max_files| max_files as usize),
+        )
+        .expect("Could not create output sink"),
+    ));
+
+    let new_live_counters =
+        || Arc::new(LiveCounters::new(events.defs.iter().map(|def| def.name.clone())));
+    let mut live_counters = new_live_counters();
+
     let mut recorder_handle = start_recorder(
-        cli_args.dst_dir_path.clone(),
         boost_enabled,
         recorder_running.clone(),
+        record_status.clone(),
+        clock.clone(),
+        events.clone(),
+        live_counters.clone(),
+        sink.clone(),
     )
     .expect("Could not start recorder");
-    let mut start_time = Instant::now();
+    let mut start_time = clock.now_mono();
 
     let loop_sleep_interval = Duration::from_secs(cli_args.main_loop_sleep_secs);
     let recording_duration = Duration::from_secs(cli_args.recording_duration_secs);
     while running.load(Ordering::SeqCst) {
         sleep(loop_sleep_interval);
-        let now = Instant::now();
-        if now - start_time > recording_duration {
+        let now = clock.now_mono();
+        if cli_args.live {
+            let line = dashboard::render_line(
+                boost_enabled,
+                now - start_time,
+                &live_counters.snapshot(),
+                cli_args.failure_rate_threshold,
+            );
+            print!("{}", line);
+            std::io::stdout().flush().ok();
+        }
+        if should_flip(now, start_time, recording_duration) {
             boost_enabled = !boost_enabled;
             set_eboost(boost_enabled);
             recorder_running.store(false, Ordering::SeqCst);
             recorder_handle
                 .join()
                 .expect("Could not join recorder thread");
+            if cli_args.live {
+                println!();
+            }
+            println!("Previous recording status: {:?}", *record_status.lock().unwrap());
             recorder_running.store(true, Ordering::SeqCst);
+            live_counters = new_live_counters();
             recorder_handle = start_recorder(
-                cli_args.dst_dir_path.clone(),
                 boost_enabled,
                 recorder_running.clone(),
+                record_status.clone(),
+                clock.clone(),
+                events.clone(),
+                live_counters.clone(),
+                sink.clone(),
             )
             .expect("Could not start recorder");
             start_time = now;
-            println!("Flip recording");
+            println!("Flip recording, boost_enabled={}", boost_enabled);
         }
     }
     recorder_running.store(false, Ordering::SeqCst);
@@ -207,3 +247,87 @@ fn main() {
         .expect("Could not join recorder thread");
     println!("Recorder joined, bye bye.");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clock::FakeClock;
+    use recording::ParseResult;
+
+    #[test]
+    fn flips_exactly_once_per_recording_duration() {
+        let clock = FakeClock::new();
+        let recording_duration = Duration::from_secs(20);
+        let mut window_start = clock.now_mono();
+        let mut flips = 0;
+
+        for _ in 0..25 {
+            clock.advance(Duration::from_secs(1));
+            let now = clock.now_mono();
+            if should_flip(now, window_start, recording_duration) {
+                flips += 1;
+                window_start = now;
+            }
+        }
+
+        assert_eq!(flips, 1);
+    }
+
+    #[test]
+    fn does_not_flip_before_recording_duration_elapses() {
+        let clock = FakeClock::new();
+        let recording_duration = Duration::from_secs(20);
+        let window_start = clock.now_mono();
+
+        clock.advance(Duration::from_secs(19));
+
+        assert!(!should_flip(clock.now_mono(), window_start, recording_duration));
+    }
+
+    #[test]
+    fn empty_recording_is_not_persisted() {
+        let dir = std::env::temp_dir().join(format!(
+            "rust-atomic-tracer-test-{}-{}",
+            std::process::id(),
+            "empty_recording_is_not_persisted"
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let clock = FakeClock::new();
+        let sink = Mutex::new(
+            RotatingSink::new(dir.to_str().unwrap(), OutputFormat::Csv, None, None).unwrap(),
+        );
+        let recording = Recording::new(clock.now_wall(), true);
+
+        save_recording_file(&sink, &recording, &clock).unwrap();
+
+        // The sink always creates the segment file with its header up front; skipping
+        // an empty recording means no data row gets appended to it.
+        let contents = std::fs::read_to_string(dir.join("recordings.0.csv")).unwrap();
+        assert_eq!(contents.lines().count(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn non_empty_recording_is_persisted() {
+        let dir = std::env::temp_dir().join(format!(
+            "rust-atomic-tracer-test-{}-{}",
+            std::process::id(),
+            "non_empty_recording_is_persisted"
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let clock = FakeClock::new();
+        let sink = Mutex::new(
+            RotatingSink::new(dir.to_str().unwrap(), OutputFormat::Csv, None, None).unwrap(),
+        );
+        let mut recording = Recording::new(clock.now_wall(), true);
+        recording.record_event("page_alloc", ParseResult::Successful);
+
+        save_recording_file(&sink, &recording, &clock).unwrap();
+
+        let contents = std::fs::read_to_string(dir.join("recordings.0.csv")).unwrap();
+        assert_eq!(contents.lines().count(), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+/// Per-event tallies of how many matched lines fell into each outcome.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EventCounters {
+    pub matched: u128,
+    pub success: u128,
+    pub failure: u128,
+    pub unparsable: u128,
+}
+
+impl EventCounters {
+    fn record(&mut self, result: &ParseResult) {
+        self.matched += 1;
+        match result {
+            ParseResult::Successful => self.success += 1,
+            ParseResult::Failed => self.failure += 1,
+            ParseResult::Unparsable => self.unparsable += 1,
+        }
+    }
+}
+
+/// A single boost-on/boost-off recording window, with tallies for every
+/// configured event it observed.
+pub struct Recording {
+    pub start_time: u128,
+    pub enabled: bool,
+    pub event_counters: HashMap<String, EventCounters>,
+}
+
+impl Recording {
+    pub fn new(start_time: u128, enabled: bool) -> Self {
+        Recording {
+            start_time,
+            enabled,
+            event_counters: HashMap::new(),
+        }
+    }
+
+    pub fn record_event(&mut self, event_name: &str, result: ParseResult) {
+        self.event_counters
+            .entry(event_name.to_string())
+            .or_default()
+            .record(&result);
+    }
+
+    /// Total matched lines across every tracked event, parsed or not.
+    pub fn total(&self) -> u128 {
+        self.event_counters.values().map(|c| c.matched).sum()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseResult {
+    Successful,
+    Failed,
+    Unparsable,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totals_sum_across_every_tracked_event() {
+        let mut recording = Recording::new(0, true);
+        recording.record_event("page_alloc", ParseResult::Successful);
+        recording.record_event("page_alloc", ParseResult::Failed);
+        recording.record_event("sched_switch", ParseResult::Successful);
+        recording.record_event("sched_switch", ParseResult::Unparsable);
+
+        assert_eq!(recording.total(), 4);
+    }
+
+    #[test]
+    fn fresh_recording_has_zero_totals() {
+        let recording = Recording::new(0, true);
+        assert_eq!(recording.total(), 0);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::writer::{create_writer_at, OutputFormat, RecordingRow, RecordingWriter};
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// Writes recordings through a rolling set of numbered segment files, rotating
+/// to the next segment once the current one exceeds `max_file_bytes` and
+/// deleting the oldest segment once more than `max_files` accumulate.
+pub struct RotatingSink {
+    dst_dir_path: String,
+    format: OutputFormat,
+    max_file_bytes: Option<u64>,
+    max_files: Option<usize>,
+    newest_segment: usize,
+    oldest_segment: usize,
+    writer: Box<dyn RecordingWriter>,
+}
+
+impl RotatingSink {
+    pub fn new(
+        dst_dir_path: &str,
+        format: OutputFormat,
+        max_file_bytes: Option<u64>,
+        max_files: Option<usize>,
+    ) -> Result<Self> {
+        if max_files == Some(0) {
+            anyhow::bail!("max_files must be at least 1, or omitted for unbounded segments");
+        }
+        let writer = create_writer_at(format, &segment_path(dst_dir_path, format, 0))?;
+        Ok(RotatingSink {
+            dst_dir_path: dst_dir_path.to_string(),
+            format,
+            max_file_bytes,
+            max_files,
+            newest_segment: 0,
+            oldest_segment: 0,
+            writer,
+        })
+    }
+
+    pub fn write_row(&mut self, row: &RecordingRow) -> Result<()> {
+        self.writer.write_row(row)?;
+        if let Some(max_file_bytes) = self.max_file_bytes {
+            if self.writer.current_size_bytes()? > max_file_bytes {
+                self.rotate()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        self.newest_segment += 1;
+        self.writer = create_writer_at(
+            self.format,
+            &segment_path(&self.dst_dir_path, self.format, self.newest_segment),
+        )?;
+        if let Some(max_files) = self.max_files {
+            while self.newest_segment - self.oldest_segment + 1 > max_files {
+                let _ = std::fs::remove_file(segment_path(
+                    &self.dst_dir_path,
+                    self.format,
+                    self.oldest_segment,
+                ));
+                self.oldest_segment += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn segment_path(dst_dir_path: &str, format: OutputFormat, segment: usize) -> PathBuf {
+    PathBuf::from(dst_dir_path).join(format!("recordings.{}.{}", segment, format.extension()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "rust-atomic-tracer-test-rotation-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn row(start_ms: u128) -> RecordingRow {
+        RecordingRow {
+            start_ms,
+            end_ms: start_ms + 1,
+            boost_enabled: true,
+            event_name: "page_alloc".to_string(),
+            successful: 1,
+            failed: 0,
+            unparsed: 0,
+        }
+    }
+
+    #[test]
+    fn rejects_max_files_zero() {
+        let dir = test_dir("rejects_max_files_zero");
+        let result = RotatingSink::new(dir.to_str().unwrap(), OutputFormat::Csv, None, Some(0));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rotation_keeps_only_max_files_segments_and_never_deletes_the_active_one() {
+        let dir = test_dir("rotation_keeps_only_max_files_segments");
+        let mut sink =
+            RotatingSink::new(dir.to_str().unwrap(), OutputFormat::Csv, Some(1), Some(2)).unwrap();
+
+        for i in 0..5 {
+            sink.write_row(&row(i)).unwrap();
+        }
+
+        assert!(!dir.join("recordings.0.csv").exists());
+        assert!(!dir.join("recordings.1.csv").exists());
+        assert!(!dir.join("recordings.2.csv").exists());
+        assert!(!dir.join("recordings.3.csv").exists());
+        assert!(dir.join("recordings.4.csv").exists());
+        // The currently active segment must always survive, even though it was
+        // just created by the same rotation that trimmed the oldest one away.
+        assert!(dir.join("recordings.5.csv").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,46 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Lifecycle of a single recorder thread, shared with `main` so the control
+/// loop can log meaningful progress instead of only "Flip recording".
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordStatus {
+    Idle,
+    Waiting,
+    Recording(Duration),
+    Finished,
+    Error(String),
+}
+
+pub type SharedRecordStatus = Arc<Mutex<RecordStatus>>;
+
+pub fn new_shared_status() -> SharedRecordStatus {
+    Arc::new(Mutex::new(RecordStatus::Idle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_idle() {
+        let status = new_shared_status();
+        assert_eq!(*status.lock().unwrap(), RecordStatus::Idle);
+    }
+
+    #[test]
+    fn transitions_reflect_the_latest_write() {
+        let status = new_shared_status();
+        *status.lock().unwrap() = RecordStatus::Recording(Duration::from_secs(3));
+        assert_eq!(
+            *status.lock().unwrap(),
+            RecordStatus::Recording(Duration::from_secs(3))
+        );
+
+        *status.lock().unwrap() = RecordStatus::Error("boom".to_string());
+        assert_eq!(
+            *status.lock().unwrap(),
+            RecordStatus::Error("boom".to_string())
+        );
+    }
+}
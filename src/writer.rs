@@ -0,0 +1,180 @@
+use crate::recording::Recording;
+use anyhow::Result;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+/// Selects the on-disk representation `RecordingWriter` instances serialize into.
+///
+/// Only `Csv` is implemented today. HDF5/Parquet backends were originally
+/// scoped for this enum, but both need real optional dependencies this source
+/// tree doesn't carry a manifest for; adding them for real is follow-up work,
+/// not something to fake behind a flag that looks supported.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum OutputFormat {
+    Csv,
+}
+
+impl OutputFormat {
+    /// File extension used for this backend's segment files.
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Csv => "csv",
+        }
+    }
+}
+
+/// One named event's tallies within a recording window, flattened to the
+/// columns every backend writes.
+pub struct RecordingRow {
+    pub start_ms: u128,
+    pub end_ms: u128,
+    pub boost_enabled: bool,
+    pub event_name: String,
+    pub successful: u128,
+    pub failed: u128,
+    pub unparsed: u128,
+}
+
+impl RecordingRow {
+    /// One row per tracked event name, so a recording with several configured
+    /// tracepoints doesn't collapse them into a single aggregate count.
+    pub fn from_recording(recording: &Recording, end_ms: u128) -> Vec<Self> {
+        let mut event_names: Vec<&String> = recording.event_counters.keys().collect();
+        event_names.sort();
+
+        event_names
+            .into_iter()
+            .map(|event_name| {
+                let counters = &recording.event_counters[event_name];
+                RecordingRow {
+                    start_ms: recording.start_time,
+                    end_ms,
+                    boost_enabled: recording.enabled,
+                    event_name: event_name.clone(),
+                    successful: counters.success,
+                    failed: counters.failure,
+                    unparsed: counters.unparsable,
+                }
+            })
+            .collect()
+    }
+}
+
+pub trait RecordingWriter: Send {
+    fn write_row(&mut self, row: &RecordingRow) -> Result<()>;
+
+    /// Current on-disk size of the segment this writer is appending to, so a
+    /// `RotatingSink` can decide when to roll over to the next segment.
+    fn current_size_bytes(&self) -> Result<u64>;
+}
+
+/// Default backend: appends CSV lines to the segment file at `path`.
+pub struct CsvWriter {
+    file: std::fs::File,
+}
+
+impl CsvWriter {
+    pub fn new(path: &Path) -> Result<Self> {
+        let is_new = !path.exists();
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if is_new {
+            file.write_all(
+                b"start_ms,end_ms,boost_enabled,event_name,successful,failed,unparsed\n",
+            )?;
+        }
+        Ok(CsvWriter { file })
+    }
+}
+
+impl RecordingWriter for CsvWriter {
+    fn write_row(&mut self, row: &RecordingRow) -> Result<()> {
+        writeln!(
+            self.file,
+            "{},{},{},{},{},{},{}",
+            row.start_ms,
+            row.end_ms,
+            row.boost_enabled,
+            row.event_name,
+            row.successful,
+            row.failed,
+            row.unparsed
+        )?;
+        Ok(())
+    }
+
+    fn current_size_bytes(&self) -> Result<u64> {
+        Ok(self.file.metadata()?.len())
+    }
+}
+
+/// Constructs the configured backend at the given segment `path`.
+pub fn create_writer_at(format: OutputFormat, path: &Path) -> Result<Box<dyn RecordingWriter>> {
+    match format {
+        OutputFormat::Csv => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            Ok(Box::new(CsvWriter::new(path)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recording::ParseResult;
+
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "rust-atomic-tracer-test-writer-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn csv_writer_writes_header_then_rows() {
+        let dir = test_dir("csv_writer_writes_header_then_rows");
+        let path = dir.join("recordings.0.csv");
+        let mut writer = create_writer_at(OutputFormat::Csv, &path).unwrap();
+        writer
+            .write_row(&RecordingRow {
+                start_ms: 0,
+                end_ms: 1000,
+                boost_enabled: true,
+                event_name: "page_alloc".to_string(),
+                successful: 3,
+                failed: 1,
+                unparsed: 0,
+            })
+            .unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines[0], "start_ms,end_ms,boost_enabled,event_name,successful,failed,unparsed");
+        assert_eq!(lines[1], "0,1000,true,page_alloc,3,1,0");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn from_recording_emits_one_row_per_event() {
+        let mut recording = Recording::new(0, true);
+        recording.record_event("kmalloc", ParseResult::Successful);
+        recording.record_event("kmalloc", ParseResult::Failed);
+        recording.record_event("mm_page_alloc", ParseResult::Unparsable);
+
+        let rows = RecordingRow::from_recording(&recording, 1000);
+
+        assert_eq!(rows.len(), 2);
+        let kmalloc = rows.iter().find(|r| r.event_name == "kmalloc").unwrap();
+        assert_eq!((kmalloc.successful, kmalloc.failed, kmalloc.unparsed), (1, 1, 0));
+        let page_alloc = rows
+            .iter()
+            .find(|r| r.event_name == "mm_page_alloc")
+            .unwrap();
+        assert_eq!((page_alloc.successful, page_alloc.failed, page_alloc.unparsed), (0, 0, 1));
+    }
+}